@@ -1,7 +1,8 @@
 use serde::{Serialize, Serializer};
 use thiserror::Error;
 
-/// A string that is guaranteed to have length between 1 and 2000 (inclusive).
+/// A non-empty string whose length is guaranteed to satisfy the [`ContentStringConfig`] it was
+/// built with (by default, between 1 and 2000 bytes inclusive).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ContentString(String);
 
@@ -9,24 +10,68 @@ pub struct ContentString(String);
 pub enum ContentStringError {
     #[error("Text is empty")]
     Empty,
-    #[error("Text is too long: {length} characters (max {max})")]
+    #[error("Text is too short: {length} (min {min})")]
+    TooShort { length: usize, min: usize },
+    #[error("Text is too long: {length} (max {max})")]
     TooLong { length: usize, max: usize },
 }
 
+/// How a [`ContentString`] measures its length when validating against the configured bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountMode {
+    /// Count the UTF-8 byte length, as returned by `str::len`.
+    Bytes,
+    /// Count the number of Unicode scalar values, as returned by `str::chars`.
+    Chars,
+}
+
+/// Length bounds applied when building a [`ContentString`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentStringConfig {
+    pub min_len: usize,
+    pub max_len: usize,
+    pub count_mode: CountMode,
+}
+
+impl Default for ContentStringConfig {
+    fn default() -> Self {
+        Self {
+            min_len: 1,
+            max_len: ContentString::MAX_LEN,
+            count_mode: CountMode::Bytes,
+        }
+    }
+}
+
 impl ContentString {
     const MAX_LEN: usize = 2000;
 
-    /// Try to construct a `ContentString` from a `String`, validating its length.
+    /// Try to construct a `ContentString` from a `String`, validating its length against the
+    /// default [`ContentStringConfig`].
     ///
     /// # Errors
     /// Will return `Err` if the string is empty or too long
     pub fn new(s: String) -> Result<Self, ContentStringError> {
-        let len = s.len();
+        Self::new_with(s, &ContentStringConfig::default())
+    }
+
+    /// Try to construct a `ContentString` from a `String`, validating its length against `config`.
+    ///
+    /// # Errors
+    /// Will return `Err` if the string is empty, shorter than `config.min_len` or longer than
+    /// `config.max_len`, as measured by `config.count_mode`.
+    pub fn new_with(s: String, config: &ContentStringConfig) -> Result<Self, ContentStringError> {
+        let len = match config.count_mode {
+            CountMode::Bytes => s.len(),
+            CountMode::Chars => s.chars().count(),
+        };
 
         if len == 0 {
             Err(ContentStringError::Empty)
-        } else if len > Self::MAX_LEN {
-            Err(ContentStringError::TooLong { length: len, max: Self::MAX_LEN })
+        } else if len < config.min_len {
+            Err(ContentStringError::TooShort { length: len, min: config.min_len })
+        } else if len > config.max_len {
+            Err(ContentStringError::TooLong { length: len, max: config.max_len })
         } else {
             Ok(Self(s))
         }
@@ -86,4 +131,27 @@ mod tests {
         let content_string = ContentString::new("Just a normal string".into()).unwrap();
         assert_eq!(content_string.0, "Just a normal string");
     }
+
+    #[test]
+    fn count_mode_disagrees_on_multibyte() {
+        let config = ContentStringConfig { min_len: 1, max_len: 3, count_mode: CountMode::Chars };
+
+        // "é" is two bytes but one character: within the char budget...
+        let content_string = ContentString::new_with("ééé".into(), &config).unwrap();
+        assert_eq!(content_string.0, "ééé");
+
+        // ...while the byte count would have rejected it.
+        let error = ContentString::new_with("ééé".into(), &ContentStringConfig {
+            count_mode: CountMode::Bytes,
+            ..config
+        }).unwrap_err();
+        assert_eq!(error, ContentStringError::TooLong { length: 6, max: 3 });
+    }
+
+    #[test]
+    fn too_short() {
+        let config = ContentStringConfig { min_len: 5, ..ContentStringConfig::default() };
+        let error = ContentString::new_with("hi".into(), &config).unwrap_err();
+        assert_eq!(error, ContentStringError::TooShort { length: 2, min: 5 });
+    }
 }
@@ -1,6 +1,43 @@
+use serde::Deserialize;
 use thiserror::Error;
 use crate::content_string::ContentStringError;
 
+/// A structured error object as reported by the server.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ApiError {
+    pub code: Option<String>,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+/// The body of an [`Error::Api`], either deserialized into an [`ApiError`] or kept as the raw
+/// response text when it is not valid JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiErrorBody {
+    Parsed(ApiError),
+    Raw(String),
+}
+
+impl ApiErrorBody {
+    /// Parse `body` into an [`ApiError`], falling back to the raw string when it is not JSON.
+    #[must_use]
+    pub fn from_body(body: String) -> Self {
+        serde_json::from_str(&body).map_or(Self::Raw(body), Self::Parsed)
+    }
+}
+
+impl std::fmt::Display for ApiErrorBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parsed(error) => match &error.code {
+                Some(code) => write!(f, "{} ({code})", error.message),
+                None => f.write_str(&error.message),
+            },
+            Self::Raw(body) => f.write_str(body),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("invalid payload text: {0}")]
@@ -12,9 +49,56 @@ pub enum Error {
     #[error("failed to (de)serialize JSON: {0}")]
     Json(#[from] serde_json::Error),
 
-    #[error("unexpected API error: status {status}, body = {body}")]
+    #[error("failed to build endpoint URL: {0}")]
+    Url(#[from] url::ParseError),
+
+    #[error("unexpected response content type: expected {expected}, found {found}")]
+    ContentType { expected: String, found: String },
+
+    #[error("response body too large: {size} bytes (limit {limit})")]
+    Overflow { size: usize, limit: usize },
+
+    #[error("unexpected API error: status {status}, {error}")]
     Api {
         status: reqwest::StatusCode,
-        body: String,
+        error: ApiErrorBody,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_structured_error() {
+        let body = r#"{"code":"rate_limited","message":"slow down","details":{"retry_after":5}}"#;
+        let error = ApiErrorBody::from_body(body.to_string());
+
+        assert_eq!(error, ApiErrorBody::Parsed(ApiError {
+            code: Some("rate_limited".to_string()),
+            message: "slow down".to_string(),
+            details: Some(serde_json::json!({ "retry_after": 5 })),
+        }));
+        assert_eq!(error.to_string(), "slow down (rate_limited)");
+    }
+
+    #[test]
+    fn parses_codeless_error() {
+        let error = ApiErrorBody::from_body(r#"{"message":"boom"}"#.to_string());
+
+        assert_eq!(error, ApiErrorBody::Parsed(ApiError {
+            code: None,
+            message: "boom".to_string(),
+            details: None,
+        }));
+        assert_eq!(error.to_string(), "boom");
+    }
+
+    #[test]
+    fn falls_back_to_raw() {
+        let error = ApiErrorBody::from_body("not json".to_string());
+
+        assert_eq!(error, ApiErrorBody::Raw("not json".to_string()));
+        assert_eq!(error.to_string(), "not json");
+    }
+}
@@ -1,17 +1,22 @@
 #![warn(clippy::all, clippy::nursery, clippy::pedantic)]
 
+use std::fmt::Display;
+
 use reqwest::{Client, IntoUrl, Response, Url};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use url::ParseError;
-use crate::content_string::ContentString;
-use crate::error::Error;
+use crate::content_string::{ContentString, ContentStringConfig};
+use crate::error::{ApiErrorBody, Error};
 
 pub mod content_string;
 mod error;
 
 pub struct MarkovChainClient {
     addr: Url,
-    client: Client
+    client: Client,
+    max_body_size: usize,
+    content_config: ContentStringConfig,
 }
 
 #[derive(Debug, Serialize)]
@@ -26,28 +31,84 @@ pub struct GeneratePayload {
 }
 
 impl MarkovChainClient {
+    /// Default upper bound on a response body, in bytes (256 KiB).
+    const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+
     /// Initialize a new client for the markov chain API
-    /// 
+    ///
     /// # Panics
-    /// Will panic if failed to convert the `addr`
+    /// Will panic if failed to convert the `addr`. Use [`try_new`](Self::try_new) for a fallible
+    /// variant.
     #[must_use]
-    pub fn new<U: IntoUrl>(addr: U) -> Self {
-        Self {
-            addr: addr.into_url().expect("Failed to convert addr"),
+    pub fn new<U: IntoUrl + Display>(addr: U) -> Self {
+        Self::try_new(addr).expect("Failed to convert addr")
+    }
+
+    /// Initialize a new client for the markov chain API, returning an error instead of panicking
+    /// on an invalid `addr`.
+    ///
+    /// # Errors
+    /// Will return [`Error::Url`] if the `addr` cannot be parsed into a URL.
+    pub fn try_new<U: IntoUrl + Display>(addr: U) -> Result<Self, Error> {
+        Ok(Self {
+            addr: Self::parse_addr(addr)?,
             client: Client::new(),
-        }
+            max_body_size: Self::DEFAULT_MAX_BODY_SIZE,
+            content_config: ContentStringConfig::default(),
+        })
     }
 
-    /// Save the text in the database
+    /// Parse an address into a base [`Url`], surfacing a malformed address as [`Error::Url`]
+    /// (the same variant as an endpoint-join failure) rather than a transport error.
+    fn parse_addr<U: Display>(addr: U) -> Result<Url, Error> {
+        Ok(Url::parse(&addr.to_string())?)
+    }
+
+    /// Build a [`ContentString`] validated against this client's configured limits.
+    ///
+    /// Build payloads through [`input_payload`](Self::input_payload) /
+    /// [`generate_payload`](Self::generate_payload) (or this helper) to honor the configured
+    /// [`ContentStringConfig`](content_string::ContentStringConfig); constructing a
+    /// [`ContentString`] directly (e.g. `InputPayload { input: s.try_into()? }`) uses
+    /// [`ContentString::new`]'s default config and bypasses the client's bounds.
     ///
     /// # Errors
-    /// Will return `Err` if an error occurred while serializing the payload or sending a request
-    /// to the server.
+    /// Will return `Err` if the text does not satisfy the client's [`ContentStringConfig`].
+    pub fn content_string(&self, s: impl Into<String>) -> Result<ContentString, Error> {
+        Ok(ContentString::new_with(s.into(), &self.content_config)?)
+    }
+
+    /// Build an [`InputPayload`] whose text is validated against this client's configured limits.
     ///
-    /// # Panics
-    /// Will panic if the function cannot parse an endpoint URL
+    /// # Errors
+    /// Will return `Err` if the text does not satisfy the client's [`ContentStringConfig`].
+    pub fn input_payload(&self, input: impl Into<String>) -> Result<InputPayload, Error> {
+        Ok(InputPayload { input: self.content_string(input)? })
+    }
+
+    /// Build a [`GeneratePayload`] whose `start` text is validated against this client's
+    /// configured limits.
+    ///
+    /// # Errors
+    /// Will return `Err` if `start` is present and does not satisfy the client's
+    /// [`ContentStringConfig`].
+    pub fn generate_payload<S: Into<String>>(
+        &self,
+        start: Option<S>,
+        max_length: Option<usize>,
+    ) -> Result<GeneratePayload, Error> {
+        let start = start.map(|s| self.content_string(s)).transpose()?;
+
+        Ok(GeneratePayload { start, max_length })
+    }
+
+    /// Save the text in the database
+    ///
+    /// # Errors
+    /// Will return `Err` if an error occurred while building the endpoint URL, serializing the
+    /// payload or sending a request to the server.
     pub async fn input(&self, payload: InputPayload) -> Result<(), Error> {
-        let endpoint = self.get_url("generate").expect("Failed to get url");
+        let endpoint = self.get_url("generate")?;
         let payload = Self::serialize(&payload)?;
 
         self.post(endpoint, payload).await?;
@@ -57,19 +118,42 @@ impl MarkovChainClient {
     /// Generate new text
     ///
     /// # Errors
-    /// Will return `Err` if an error occurred while serializing the payload, sending a request
-    /// to the server or reading the response body.
+    /// Will return `Err` if an error occurred while building the endpoint URL, serializing the
+    /// payload, sending a request to the server, reading the response body or if the body exceeds
+    /// the configured size limit.
     ///
-    /// # Panics
-    /// Will panic if the function cannot parse an endpoint URL
+    /// Like [`generate_as`](Self::generate_as), the body is streamed against the configured size
+    /// cap, so a chunked response that omits `Content-Length` cannot force unbounded buffering.
     pub async fn generate(&self, payload: GeneratePayload) -> Result<String, Error> {
-        let endpoint = self.get_url("generate").expect("Failed to get url");
+        let endpoint = self.get_url("generate")?;
         let payload = Self::serialize(&payload)?;
 
         let response = self.post(endpoint, payload).await?;
-        let text = response.text().await?;
+        self.check_headers(&response, None)?;
+        let bytes = self.read_capped(response).await?;
 
-        Ok(text)
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Generate new text and deserialize the response body into `T`
+    ///
+    /// Use this instead of [`generate`](Self::generate) when the server replies with a
+    /// structured JSON object (e.g. `{ "text": "...", "tokens_used": N }`) rather than plain
+    /// text.
+    ///
+    /// # Errors
+    /// Will return `Err` if an error occurred while building the endpoint URL, serializing the
+    /// payload, sending a request to the server, reading the response body or deserializing it
+    /// into `T`.
+    pub async fn generate_as<T: DeserializeOwned>(&self, payload: GeneratePayload) -> Result<T, Error> {
+        let endpoint = self.get_url("generate")?;
+        let payload = Self::serialize(&payload)?;
+
+        let response = self.post(endpoint, payload).await?;
+        self.check_headers(&response, Some("application/json"))?;
+        let bytes = self.read_capped(response).await?;
+
+        serde_json::from_slice(&bytes).map_err(Error::Json)
     }
 
     async fn post<U: IntoUrl>(&self, endpoint: U, payload: String) -> Result<Response, Error> {
@@ -84,13 +168,79 @@ impl MarkovChainClient {
         if status.is_success() {
             Ok(response)
         } else {
+            let bytes = self.read_capped(response).await?;
+            let body = String::from_utf8_lossy(&bytes).into_owned();
+
             Err(Error::Api {
                 status,
-                body: response.text().await?
+                error: ApiErrorBody::from_body(body),
             })
         }
     }
 
+    /// Buffer a response body, streaming chunk by chunk so the accumulated size never exceeds
+    /// the configured limit.
+    ///
+    /// This bounds memory even for responses that omit `Content-Length` (e.g. chunked transfer
+    /// encoding), returning [`Error::Overflow`] as soon as the cap is crossed rather than after
+    /// the whole body has been buffered.
+    async fn read_capped(&self, mut response: Response) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > self.max_body_size {
+                return Err(Error::Overflow {
+                    size: body.len() + chunk.len(),
+                    limit: self.max_body_size,
+                });
+            }
+
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
+    /// Validate a response's headers before its body is buffered.
+    ///
+    /// When `expected` is `Some`, the response's `Content-Type` (ignoring any parameters such as
+    /// `; charset=utf-8`) must match it, otherwise an [`Error::ContentType`] is returned. The
+    /// advertised `Content-Length`, when present, is checked against the configured body-size
+    /// limit so an oversized body is rejected before a single byte is read.
+    fn check_headers(&self, response: &Response, expected: Option<&str>) -> Result<(), Error> {
+        if let Some(expected) = expected {
+            let found = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+
+            if !Self::content_type_matches(found, expected) {
+                return Err(Error::ContentType {
+                    expected: expected.to_string(),
+                    found: found.to_string(),
+                });
+            }
+        }
+
+        if let Some(size) = response.content_length() {
+            let size = usize::try_from(size).unwrap_or(usize::MAX);
+
+            if size > self.max_body_size {
+                return Err(Error::Overflow { size, limit: self.max_body_size });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a response's `Content-Type` header matches `expected`, ignoring any parameters
+    /// (such as `; charset=utf-8`) and ASCII case.
+    fn content_type_matches(found: &str, expected: &str) -> bool {
+        let mime = found.split(';').next().unwrap_or_default().trim();
+        mime.eq_ignore_ascii_case(expected)
+    }
+
     fn serialize<T: ?Sized + Serialize>(value: &T) -> Result<String, Error> {
         serde_json::to_string(&value).map_err(Error::Json)
     }
@@ -100,6 +250,101 @@ impl MarkovChainClient {
     }
 }
 
+/// Builder for configuring a [`MarkovChainClient`] and the underlying [`reqwest::Client`].
+///
+/// Supply a pre-built [`reqwest::Client`] with [`client`](Self::client) to take full control of
+/// the transport, or use the convenience setters ([`timeout`](Self::timeout),
+/// [`default_headers`](Self::default_headers)) to tweak a client built for you.
+#[derive(Debug)]
+pub struct MarkovChainClientBuilder<U: IntoUrl + Display> {
+    addr: U,
+    client: Option<Client>,
+    timeout: Option<std::time::Duration>,
+    default_headers: Option<reqwest::header::HeaderMap>,
+    max_body_size: usize,
+    content_config: ContentStringConfig,
+}
+
+impl<U: IntoUrl + Display> MarkovChainClientBuilder<U> {
+    /// Start building a client for the markov chain API at `addr`.
+    #[must_use]
+    pub fn new(addr: U) -> Self {
+        Self {
+            addr,
+            client: None,
+            timeout: None,
+            default_headers: None,
+            max_body_size: MarkovChainClient::DEFAULT_MAX_BODY_SIZE,
+            content_config: ContentStringConfig::default(),
+        }
+    }
+
+    /// Use a pre-built [`reqwest::Client`], ignoring the `timeout`/`default_headers` setters.
+    #[must_use]
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set a request timeout for a client built by this builder.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the default headers (e.g. an auth token) sent with every request.
+    #[must_use]
+    pub fn default_headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.default_headers = Some(headers);
+        self
+    }
+
+    /// Set the maximum response body size, in bytes, buffered before deserializing.
+    #[must_use]
+    pub const fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Set the [`ContentStringConfig`] used when building payload text through the client.
+    #[must_use]
+    pub const fn content_config(mut self, content_config: ContentStringConfig) -> Self {
+        self.content_config = content_config;
+        self
+    }
+
+    /// Build the [`MarkovChainClient`].
+    ///
+    /// # Errors
+    /// Will return [`Error::Url`] if the `addr` cannot be parsed into a URL, or the underlying
+    /// error if the [`reqwest::Client`] fails to build.
+    pub fn build(self) -> Result<MarkovChainClient, Error> {
+        let client = if let Some(client) = self.client {
+            client
+        } else {
+            let mut builder = Client::builder();
+
+            if let Some(timeout) = self.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            if let Some(headers) = self.default_headers {
+                builder = builder.default_headers(headers);
+            }
+
+            builder.build()?
+        };
+
+        Ok(MarkovChainClient {
+            addr: MarkovChainClient::parse_addr(self.addr)?,
+            client,
+            max_body_size: self.max_body_size,
+            content_config: self.content_config,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::{json, Value};
@@ -148,4 +393,27 @@ mod tests {
         payload.max_length = Some(42);
         assert(&json, &payload);
     }
+
+    #[test]
+    fn try_new_rejects_malformed_addr() {
+        let error = MarkovChainClient::try_new("not a url").unwrap_err();
+        assert!(matches!(error, Error::Url(_)), "expected Error::Url, got {error:?}");
+    }
+
+    #[test]
+    fn try_new_accepts_valid_addr() {
+        assert!(MarkovChainClient::try_new("http://localhost:8080/").is_ok());
+    }
+
+    #[test]
+    fn content_type_matches_ignores_params_and_case() {
+        assert!(MarkovChainClient::content_type_matches("application/json", "application/json"));
+        assert!(MarkovChainClient::content_type_matches("Application/JSON; charset=utf-8", "application/json"));
+    }
+
+    #[test]
+    fn content_type_rejects_mismatch() {
+        assert!(!MarkovChainClient::content_type_matches("text/plain", "application/json"));
+        assert!(!MarkovChainClient::content_type_matches("", "application/json"));
+    }
 }